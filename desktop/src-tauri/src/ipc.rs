@@ -0,0 +1,321 @@
+// Single-instance IPC: a Unix domain socket (or Windows named pipe) acts as a
+// rendezvous point so a second launch of the app can hand its CLI args to the
+// already-running primary instance instead of spawning a second whodb-core.
+// The socket and lock file use fixed names under the directory the caller
+// picks (the system temp dir in `main`), so where that directory is shared
+// between users, these names are shared too.
+
+use std::path::Path;
+
+pub enum Instance {
+    /// This is the first (primary) instance. Holds the listener so the bind
+    /// stays alive for the app's lifetime; pass it to `spawn_listener`.
+    Primary(Listener),
+    /// Another instance holds the channel or the lock, or binding failed. Our
+    /// CLI args were sent only if a listener was already accepting connections.
+    Secondary,
+}
+
+/// Binds the single-instance rendezvous channel, or if another instance
+/// already holds it, forwards this process's CLI args to it and returns
+/// `Secondary`.
+///
+/// `lock_path` is held exclusively (`flock` on Unix) for the lifetime of the
+/// primary instance, so two processes launched at the same instant - both
+/// seeing no live listener yet - can't both win the race to bind `ipc_path`.
+/// The loser of that race, like a process whose bind fails for any other
+/// reason, also gets `Secondary`, without its args having been sent.
+pub fn acquire(lock_path: &Path, ipc_path: &Path, args: &[String]) -> Instance {
+    if platform::send_to_existing(ipc_path, args) {
+        return Instance::Secondary;
+    }
+
+    match platform::bind(lock_path, ipc_path) {
+        Some(listener) => Instance::Primary(Listener(listener)),
+        None => Instance::Secondary,
+    }
+}
+
+/// Spawns a thread that accepts connections on `listener` and invokes
+/// `on_args` with each secondary instance's forwarded CLI args, newline
+/// terminated by an empty line.
+pub fn spawn_listener<F>(listener: Listener, on_args: F)
+where
+    F: Fn(Vec<String>) + Send + 'static,
+{
+    platform::spawn_listener(listener.0, on_args);
+}
+
+/// Removes the socket/pipe and lock-file artifacts created by `acquire`.
+/// Best-effort, called on shutdown.
+pub fn cleanup(lock_path: &Path, ipc_path: &Path) {
+    let _ = std::fs::remove_file(lock_path);
+    platform::cleanup(ipc_path);
+}
+
+pub struct Listener(platform::RawListener);
+
+#[cfg(unix)]
+mod platform {
+    use std::fs;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::io::AsRawFd;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::Path;
+
+    pub struct RawListener {
+        listener: UnixListener,
+        // Held for as long as this process is primary; releasing it (by
+        // dropping this field) is what lets a later launch win the lock.
+        _lock_file: fs::File,
+    }
+
+    pub fn send_to_existing(socket_path: &Path, args: &[String]) -> bool {
+        let Ok(mut stream) = UnixStream::connect(socket_path) else {
+            return false;
+        };
+        let payload = args.join("\n");
+        let _ = stream.write_all(payload.as_bytes());
+        let _ = stream.write_all(b"\n\n");
+        true
+    }
+
+    // Takes an exclusive, non-blocking `flock` on `lock_path`, succeeding only
+    // if no other live process holds it. Two processes launched at the same
+    // instant both fall through to this after finding no live socket to
+    // connect to; only one of them gets to proceed to `bind`.
+    fn acquire_exclusive_lock(lock_path: &Path) -> Option<fs::File> {
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .open(lock_path)
+            .ok()?;
+
+        // SAFETY: `file`'s fd is valid for the duration of this call.
+        // LOCK_EX | LOCK_NB returns immediately with EWOULDBLOCK instead of
+        // blocking if another live process already holds the lock.
+        let locked = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0;
+        if !locked {
+            return None;
+        }
+
+        let _ = file.set_len(0);
+        let mut handle = &file;
+        let _ = write!(handle, "{}", std::process::id());
+        Some(file)
+    }
+
+    pub fn bind(lock_path: &Path, socket_path: &Path) -> Option<RawListener> {
+        let lock_file = acquire_exclusive_lock(lock_path)?;
+
+        // We hold the exclusive lock, so no other process can be concurrently
+        // binding right now; it's safe to clear a stale socket file left
+        // behind by a crash before binding fresh.
+        let _ = fs::remove_file(socket_path);
+
+        let listener = UnixListener::bind(socket_path).ok()?;
+
+        // Restrict the socket to the current user (owner read/write only).
+        // The lock file keeps the default permissions it was created with.
+        if let Ok(metadata) = fs::metadata(socket_path) {
+            let mut perms = metadata.permissions();
+            perms.set_mode(0o600);
+            let _ = fs::set_permissions(socket_path, perms);
+        }
+
+        Some(RawListener {
+            listener,
+            _lock_file: lock_file,
+        })
+    }
+
+    pub fn spawn_listener<F>(listener: RawListener, on_args: F)
+    where
+        F: Fn(Vec<String>) + Send + 'static,
+    {
+        std::thread::spawn(move || {
+            for stream in listener.listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                on_args(read_args(stream));
+            }
+        });
+    }
+
+    fn read_args(stream: UnixStream) -> Vec<String> {
+        let mut reader = BufReader::new(stream);
+        let mut args = Vec::new();
+        loop {
+            let mut line = String::new();
+            match reader.read_line(&mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    let line = line.trim_end_matches(['\n', '\r']).to_string();
+                    if line.is_empty() {
+                        break;
+                    }
+                    args.push(line);
+                }
+            }
+        }
+        args
+    }
+
+    pub fn cleanup(socket_path: &Path) {
+        let _ = fs::remove_file(socket_path);
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::ffi::c_void;
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+    use windows_sys::Win32::Foundation::{CloseHandle, GetLastError, HANDLE, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::Storage::FileSystem::{
+        CreateFileW, ReadFile, WriteFile, FILE_GENERIC_WRITE, OPEN_EXISTING,
+    };
+    use windows_sys::Win32::System::Pipes::{
+        ConnectNamedPipe, CreateNamedPipeW, DisconnectNamedPipe, PIPE_ACCESS_DUPLEX,
+        PIPE_READMODE_BYTE, PIPE_TYPE_BYTE, PIPE_WAIT,
+    };
+
+    // A Windows named pipe is identified by name, not by a filesystem path, so
+    // `ipc_path` (a path under the temp dir on every platform) is only used to
+    // derive a stable pipe name from its file stem. Pipe names are
+    // machine-wide, so every user gets the same name.
+    const PIPE_PREFIX: &str = r"\\.\pipe\whodb-desktop-";
+
+    pub struct RawListener(HANDLE);
+    unsafe impl Send for RawListener {}
+
+    fn pipe_name(ipc_path: &Path) -> Vec<u16> {
+        let suffix = ipc_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("singleton");
+        let name = format!("{}{}", PIPE_PREFIX, suffix);
+        OsStr::new(&name).encode_wide().chain(std::iter::once(0)).collect()
+    }
+
+    pub fn send_to_existing(ipc_path: &Path, args: &[String]) -> bool {
+        let wide_name = pipe_name(ipc_path);
+        let handle = unsafe {
+            CreateFileW(
+                wide_name.as_ptr(),
+                FILE_GENERIC_WRITE,
+                0,
+                std::ptr::null(),
+                OPEN_EXISTING,
+                0,
+                0,
+            )
+        };
+        if handle == INVALID_HANDLE_VALUE {
+            return false;
+        }
+
+        let payload = format!("{}\n\n", args.join("\n"));
+        let bytes = payload.as_bytes();
+        let mut written = 0u32;
+        unsafe {
+            WriteFile(
+                handle,
+                bytes.as_ptr() as *const c_void,
+                bytes.len() as u32,
+                &mut written,
+                std::ptr::null_mut(),
+            );
+            CloseHandle(handle);
+        }
+        true
+    }
+
+    // `lock_path` is unused here: `CreateNamedPipeW` with `nMaxInstances` of 1
+    // is already an atomic create-or-fail, so Windows doesn't need the extra
+    // lock file Unix relies on. Kept in the signature so `acquire` can call
+    // both platforms' `bind` uniformly.
+    pub fn bind(_lock_path: &Path, ipc_path: &Path) -> Option<RawListener> {
+        let wide_name = pipe_name(ipc_path);
+        let handle = unsafe {
+            CreateNamedPipeW(
+                wide_name.as_ptr(),
+                PIPE_ACCESS_DUPLEX,
+                PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT,
+                1,
+                4096,
+                4096,
+                0,
+                std::ptr::null(),
+            )
+        };
+        if handle == INVALID_HANDLE_VALUE {
+            // ERROR_PIPE_BUSY means another instance already owns the pipe.
+            let _ = unsafe { GetLastError() };
+            return None;
+        }
+        Some(RawListener(handle))
+    }
+
+    pub fn spawn_listener<F>(listener: RawListener, on_args: F)
+    where
+        F: Fn(Vec<String>) + Send + 'static,
+    {
+        std::thread::spawn(move || loop {
+            let handle = listener.0;
+            let connected = unsafe { ConnectNamedPipe(handle, std::ptr::null_mut()) };
+            if connected == 0 {
+                // Treat ERROR_PIPE_CONNECTED (client connected between the
+                // create and the connect call) as success; anything else,
+                // keep serving rather than tearing the whole thread down.
+                if unsafe { GetLastError() } != windows_sys::Win32::Foundation::ERROR_PIPE_CONNECTED {
+                    continue;
+                }
+            }
+
+            let mut buf = [0u8; 4096];
+            let mut payload = Vec::new();
+            loop {
+                let mut read = 0u32;
+                let ok = unsafe {
+                    ReadFile(
+                        handle,
+                        buf.as_mut_ptr() as *mut c_void,
+                        buf.len() as u32,
+                        &mut read,
+                        std::ptr::null_mut(),
+                    )
+                };
+                if ok == 0 || read == 0 {
+                    break;
+                }
+                payload.extend_from_slice(&buf[..read as usize]);
+                if payload.ends_with(b"\n\n") {
+                    break;
+                }
+            }
+
+            let text = String::from_utf8_lossy(&payload);
+            let args = text
+                .split('\n')
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect();
+            on_args(args);
+
+            // Without this, every instance after the second finds the pipe
+            // still "connected" to the previous client and ConnectNamedPipe
+            // never blocks for a new one.
+            unsafe { DisconnectNamedPipe(handle) };
+        });
+    }
+
+    pub fn cleanup(ipc_path: &Path) {
+        // The pipe itself is closed when the listener's owning thread exits
+        // with the process; nothing is left on disk to remove, unlike the
+        // Unix socket file.
+        let _ = ipc_path;
+    }
+}
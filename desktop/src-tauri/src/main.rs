@@ -2,13 +2,45 @@
 // Comment out the line below to see console output in release builds
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod ipc;
+mod vault;
+
 use serde::{Deserialize, Serialize};
-use std::net::TcpListener;
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
 use std::sync::Mutex;
 use std::thread;
-use std::time::Duration;
-use tauri::Manager;
+use std::time::{Duration, Instant};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Emitter, Manager};
+
+// Binary name we look for under each resolution strategy, platform-suffixed.
+const CORE_BINARY_NAME: &str = "whodb-core";
+
+// How often to probe the backend port while waiting for it to come up.
+const READY_POLL_INTERVAL: Duration = Duration::from_millis(50);
+// Total time to wait for the backend to start accepting connections.
+const READY_TIMEOUT: Duration = Duration::from_secs(15);
+
+// How often the supervisor checks whether the backend process is still alive.
+const SUPERVISOR_POLL_INTERVAL: Duration = Duration::from_secs(1);
+// Backoff applied between restart attempts after an unexpected exit.
+const RESTART_BACKOFF_INITIAL: Duration = Duration::from_millis(500);
+const RESTART_BACKOFF_MAX: Duration = Duration::from_secs(30);
+// A process that stays up at least this long counts as a healthy run, resetting backoff.
+const RESTART_BACKOFF_RESET_AFTER: Duration = Duration::from_secs(60);
+// Give up after this many consecutive failed restarts.
+const MAX_CONSECUTIVE_RESTARTS: u32 = 10;
+
+// How long to wait for a graceful exit before escalating to a hard kill.
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(5);
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// Spawning the backend into its own process group is what makes
+// GenerateConsoleCtrlEvent(CTRL_BREAK_EVENT, ...) valid in `request_graceful_stop`.
+#[cfg(windows)]
+const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct BackendInfo {
@@ -19,6 +51,9 @@ struct BackendInfo {
 // Global state to track the backend process
 static BACKEND_INFO: Mutex<Option<BackendInfo>> = Mutex::new(None);
 static BACKEND_CHILD: Mutex<Option<Child>> = Mutex::new(None);
+// Paths of the single-instance lock file and IPC socket, set once in `main`
+// and used by `cleanup_backend`: (lock_path, socket_path).
+static SINGLE_INSTANCE_PATHS: Mutex<Option<(PathBuf, PathBuf)>> = Mutex::new(None);
 
 // Learn more about Tauri commands at https://tauri.app/v1/guides/features/command
 #[tauri::command]
@@ -46,29 +81,66 @@ fn find_available_port() -> Result<u16, Box<dyn std::error::Error>> {
     Ok(addr.port())
 }
 
-fn start_backend() -> Result<BackendInfo, Box<dyn std::error::Error>> {
-    println!("[DEBUG] Starting backend process...");
+// Searches `PATH` for an externally installed `whodb-core`, the way a shell's
+// `which` would.
+fn find_on_path() -> Option<PathBuf> {
+    let path_var = std::env::var_os("PATH")?;
+    let name = if cfg!(windows) {
+        format!("{}.exe", CORE_BINARY_NAME)
+    } else {
+        CORE_BINARY_NAME.to_string()
+    };
 
-    // Find an available port
-    let port = find_available_port()?;
-    println!("[DEBUG] Found available port: {}", port);
+    std::env::split_paths(&path_var)
+        .map(|dir| dir.join(&name))
+        .find(|candidate| candidate.exists())
+}
+
+// Locates the `whodb-core` binary, trying strategies in order of preference:
+// 1. The app's bundled resource directory (via Tauri's resource-dir API),
+//    which is where `whodb-core` ends up in packaged builds. This is a
+//    resource lookup, not Tauri's `externalBin`/sidecar mechanism - nothing
+//    here declares `whodb-core` as a sidecar in `tauri.conf.json`.
+// 2. A `whodb-core` found on `PATH`, for users who installed it separately.
+// 3. The historical hand-rolled candidate paths, for local dev layouts.
+//
+// Returns an error that names the `PATH` search and lists every other path
+// that was tried.
+fn resolve_core_binary(app: Option<&AppHandle>) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let mut tried = Vec::new();
 
-    // Get the path to the core binary
+    if let Some(app) = app {
+        let resource_name = format!("{}{}", CORE_BINARY_NAME, std::env::consts::EXE_SUFFIX);
+        match app.path().resolve(&resource_name, BaseDirectory::Resource) {
+            Ok(path) => {
+                tried.push(path.clone());
+                if path.exists() {
+                    return Ok(path);
+                }
+            }
+            Err(e) => {
+                eprintln!("[DEBUG] Resource-dir resolution failed: {}", e);
+            }
+        }
+    }
+
+    if let Some(path) = find_on_path() {
+        tried.push(path.clone());
+        return Ok(path);
+    }
+
+    // Fall back to the historical candidate list for local dev layouts.
     let exe_path = std::env::current_exe()?;
     let exe_dir = exe_path
         .parent()
         .ok_or("Could not get executable directory")?;
 
-    // Try different possible locations for the core binary across platforms
-    // On Windows during development, prefer the bin directory over target directory
     let exe_candidates = ["whodb-core", "whodb-core.exe", "bin/whodb-core", "bin/whodb-core.exe"];
     let mut possible_paths = Vec::new();
 
-    // First check the bin directory relative to src-tauri
     if cfg!(debug_assertions) {
-        // In debug mode, look in src-tauri/bin first
         if let Some(manifest_dir) = option_env!("CARGO_MANIFEST_DIR") {
-            let manifest_path = std::path::Path::new(manifest_dir);
+            let manifest_path = Path::new(manifest_dir);
             possible_paths.push(manifest_path.join("bin").join("whodb-core.exe"));
             possible_paths.push(manifest_path.join("bin").join("whodb-core"));
         }
@@ -80,22 +152,35 @@ fn start_backend() -> Result<BackendInfo, Box<dyn std::error::Error>> {
         possible_paths.push(exe_dir.join("..").join("resources").join(name));
     }
 
-    let mut core_binary = None;
-    for path in &possible_paths {
-        if path.exists() {
-            core_binary = Some(path.clone());
-            break;
-        }
-    }
+    let core_binary = possible_paths.iter().find(|path| path.exists()).cloned();
+    tried.extend(possible_paths);
 
-    let core_binary = core_binary.ok_or_else(|| {
+    core_binary.ok_or_else(|| {
         eprintln!("[ERROR] Core binary not found. Searched paths:");
-        for path in &possible_paths {
+        for path in &tried {
             eprintln!("  - {}", path.display());
         }
-        "Core binary not found in any expected location"
-    })?;
+        format!(
+            "Core binary not found on PATH or in any of {} searched location(s): {}",
+            tried.len(),
+            tried
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+        .into()
+    })
+}
 
+fn start_backend(app: Option<&AppHandle>) -> Result<BackendInfo, Box<dyn std::error::Error>> {
+    println!("[DEBUG] Starting backend process...");
+
+    // Find an available port
+    let port = find_available_port()?;
+    println!("[DEBUG] Found available port: {}", port);
+
+    let core_binary = resolve_core_binary(app)?;
     println!("[DEBUG] Found core binary at: {}", core_binary.display());
 
     // Start the backend process with the random port
@@ -103,7 +188,8 @@ fn start_backend() -> Result<BackendInfo, Box<dyn std::error::Error>> {
     println!("[DEBUG] With PORT={}", port);
     println!("[DEBUG] With WHODB_ALLOWED_ORIGINS=tauri://*,taur://*,app://*,http://localhost:1420,http://localhost:*,https://*");
 
-    let child = Command::new(&core_binary)
+    let mut command = Command::new(&core_binary);
+    command
         .env("PORT", port.to_string())
         .env(
             "WHODB_ALLOWED_ORIGINS",
@@ -112,8 +198,18 @@ fn start_backend() -> Result<BackendInfo, Box<dyn std::error::Error>> {
             "tauri://*,taur://*,app://*,http://localhost:1420,http://localhost:*,https://*",
         )
         .stdout(Stdio::inherit())  // Changed to inherit to see output
-        .stderr(Stdio::inherit())  // Changed to inherit to see output
-        .spawn()?;
+        .stderr(Stdio::inherit()); // Changed to inherit to see output
+
+    // Spawn into its own process group so `request_graceful_stop`'s
+    // GenerateConsoleCtrlEvent(CTRL_BREAK_EVENT, ...) targets this process
+    // alone instead of being invalid without a group to address.
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(CREATE_NEW_PROCESS_GROUP);
+    }
+
+    let child = command.spawn()?;
 
     let pid = child.id();
     println!("[DEBUG] Backend process started with PID: {}", pid);
@@ -123,49 +219,225 @@ fn start_backend() -> Result<BackendInfo, Box<dyn std::error::Error>> {
         *child_guard = Some(child);
     }
 
-    // Give the process a moment to start
-    thread::sleep(Duration::from_millis(1000));
+    // Poll the port instead of sleeping a flat duration - this returns as soon as
+    // whodb-core is actually listening, and fails fast if it crashes on startup.
+    wait_for_backend_ready(port)?;
+    println!("[DEBUG] Backend process is running successfully");
 
-    // Check if the process is still running
-    if let Ok(mut child_guard) = BACKEND_CHILD.lock() {
-        if let Some(ref mut child) = *child_guard {
-            match child.try_wait() {
-                Ok(Some(status)) => {
-                    eprintln!("[ERROR] Backend process exited immediately!");
-                    eprintln!("[ERROR] Exit status: {:?}", status);
-
-                    // Note: Can't read stderr since we're using inherit mode
-
-                    return Err(format!(
-                        "Backend process exited immediately with status: {:?}",
-                        status
-                    )
-                    .into());
+    Ok(BackendInfo {
+        port,
+        pid: Some(pid),
+    })
+}
+
+// Repeatedly probes `127.0.0.1:{port}` until it accepts a TCP connection, the
+// child process exits (reported as an error), or `READY_TIMEOUT` elapses.
+fn wait_for_backend_ready(port: u16) -> Result<(), Box<dyn std::error::Error>> {
+    let deadline = Instant::now() + READY_TIMEOUT;
+    let addr = format!("127.0.0.1:{}", port);
+
+    loop {
+        if TcpStream::connect(&addr).is_ok() {
+            return Ok(());
+        }
+
+        if let Ok(mut child_guard) = BACKEND_CHILD.lock() {
+            if let Some(ref mut child) = *child_guard {
+                match child.try_wait() {
+                    Ok(Some(status)) => {
+                        eprintln!("[ERROR] Backend process exited before becoming ready!");
+                        eprintln!("[ERROR] Exit status: {:?}", status);
+                        return Err(format!(
+                            "Backend process exited before becoming ready, status: {:?}",
+                            status
+                        )
+                        .into());
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        return Err(format!("Error checking backend process: {}", e).into());
+                    }
                 }
-                Ok(None) => {
-                    println!("[DEBUG] Backend process is running successfully");
+            }
+        }
+
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "Backend did not become ready on {} within {:?}",
+                addr, READY_TIMEOUT
+            )
+            .into());
+        }
+
+        thread::sleep(READY_POLL_INTERVAL);
+    }
+}
+
+// Watches the backend process and restarts it with exponential backoff if it
+// exits unexpectedly. Runs for the lifetime of the app; emits `backend-restarted`
+// with the new port on a successful restart, or `backend-failed` once retries
+// are exhausted.
+fn spawn_supervisor(app: AppHandle) {
+    thread::spawn(move || {
+        let mut backoff = RESTART_BACKOFF_INITIAL;
+        let mut consecutive_restarts: u32 = 0;
+
+        loop {
+            thread::sleep(SUPERVISOR_POLL_INTERVAL);
+
+            let exited = {
+                let mut child_guard = match BACKEND_CHILD.lock() {
+                    Ok(guard) => guard,
+                    Err(_) => continue,
+                };
+                match child_guard.as_mut() {
+                    Some(child) => matches!(child.try_wait(), Ok(Some(_))),
+                    None => false,
+                }
+            };
+
+            if !exited {
+                continue;
+            }
+
+            eprintln!("[ERROR] Backend process exited unexpectedly, attempting restart...");
+            if let Ok(mut child_guard) = BACKEND_CHILD.lock() {
+                *child_guard = None;
+            }
+
+            match start_backend(Some(&app)) {
+                Ok(backend_info) => {
+                    println!(
+                        "🚀 Restarted WhoDB backend on port {}",
+                        backend_info.port
+                    );
+                    let port = backend_info.port;
+                    if let Ok(mut info) = BACKEND_INFO.lock() {
+                        *info = Some(backend_info);
+                    }
+                    let _ = app.emit("backend-restarted", port);
+
+                    // Only treat this as a genuine recovery (and reset backoff) once
+                    // the new process has stayed up for a while; keep polling in
+                    // small increments the whole time so a second crash is caught
+                    // within `SUPERVISOR_POLL_INTERVAL`, not only after the full
+                    // reset window elapses.
+                    let reset_deadline = Instant::now() + RESTART_BACKOFF_RESET_AFTER;
+                    let mut crashed_again = false;
+                    while Instant::now() < reset_deadline {
+                        thread::sleep(SUPERVISOR_POLL_INTERVAL);
+                        let exited_again = BACKEND_CHILD
+                            .lock()
+                            .ok()
+                            .and_then(|mut guard| guard.as_mut().map(|c| c.try_wait()))
+                            .map(|r| matches!(r, Ok(Some(_))))
+                            .unwrap_or(false);
+                        if exited_again {
+                            crashed_again = true;
+                            break;
+                        }
+                    }
+
+                    if crashed_again {
+                        thread::sleep(backoff);
+                        backoff = std::cmp::min(backoff * 2, RESTART_BACKOFF_MAX);
+                    } else {
+                        backoff = RESTART_BACKOFF_INITIAL;
+                        consecutive_restarts = 0;
+                    }
                 }
                 Err(e) => {
-                    return Err(format!("Error checking backend process: {}", e).into());
+                    eprintln!("[ERROR] Failed to restart backend: {}", e);
+                    consecutive_restarts += 1;
+
+                    if consecutive_restarts >= MAX_CONSECUTIVE_RESTARTS {
+                        eprintln!(
+                            "[ERROR] Giving up after {} consecutive failed restarts",
+                            consecutive_restarts
+                        );
+                        let _ = app.emit("backend-failed", e.to_string());
+                        return;
+                    }
+
+                    thread::sleep(backoff);
+                    backoff = std::cmp::min(backoff * 2, RESTART_BACKOFF_MAX);
                 }
             }
         }
+    });
+}
+
+// Sends the child a termination request it can act on before dying: SIGTERM on
+// Unix, a console break event on Windows. Best-effort - a failure here just
+// means we fall straight through to the grace-period timeout and a hard kill.
+fn request_graceful_stop(child: &Child) {
+    #[cfg(unix)]
+    {
+        // SAFETY: `pid` is our own tracked child's pid; SIGTERM asks it to
+        // shut down cleanly instead of forcibly terminating it.
+        unsafe {
+            libc::kill(child.id() as libc::pid_t, libc::SIGTERM);
+        }
     }
 
-    Ok(BackendInfo {
-        port,
-        pid: Some(pid),
-    })
+    #[cfg(windows)]
+    {
+        // SAFETY: `pid` is our own tracked child's pid, spawned into its own
+        // process group (whose id is that pid) so it can receive the break event.
+        unsafe {
+            windows_sys::Win32::System::Console::GenerateConsoleCtrlEvent(
+                windows_sys::Win32::System::Console::CTRL_BREAK_EVENT,
+                child.id(),
+            );
+        }
+    }
+}
+
+// Asks `child` to stop gracefully, waits up to `SHUTDOWN_GRACE_PERIOD` for it
+// to exit on its own, and only then escalates to a hard kill.
+fn stop_child_gracefully(mut child: Child) {
+    request_graceful_stop(&child);
+
+    let deadline = Instant::now() + SHUTDOWN_GRACE_PERIOD;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => {
+                println!("✅ Backend process exited gracefully");
+                return;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                eprintln!("❌ Error waiting for backend process to exit: {}", e);
+                break;
+            }
+        }
+
+        if Instant::now() >= deadline {
+            eprintln!("⚠️ Backend did not exit within the grace period, forcing termination");
+            break;
+        }
+
+        thread::sleep(SHUTDOWN_POLL_INTERVAL);
+    }
+
+    match child.kill() {
+        Ok(_) => println!("✅ Backend process terminated"),
+        Err(e) => eprintln!("❌ Failed to terminate backend process: {}", e),
+    }
+    let _ = child.wait();
 }
 
 fn cleanup_backend() {
     println!("🧹 Cleaning up backend process...");
     if let Ok(mut child_guard) = BACKEND_CHILD.lock() {
-        if let Some(mut child) = child_guard.take() {
-            match child.kill() {
-                Ok(_) => println!("✅ Backend process terminated"),
-                Err(e) => eprintln!("❌ Failed to terminate backend process: {}", e),
-            }
+        if let Some(child) = child_guard.take() {
+            stop_child_gracefully(child);
+        }
+    }
+
+    if let Ok(mut paths) = SINGLE_INSTANCE_PATHS.lock() {
+        if let Some((lock_path, socket_path)) = paths.take() {
+            ipc::cleanup(&lock_path, &socket_path);
         }
     }
 }
@@ -176,31 +448,43 @@ fn main() {
         cleanup_backend();
     };
 
-    // Start the backend process
-    match start_backend() {
-        Ok(backend_info) => {
-            println!("🚀 Started WhoDB backend on port {}", backend_info.port);
-
-            // Store the backend info globally
-            if let Ok(mut info) = BACKEND_INFO.lock() {
-                *info = Some(backend_info);
-            }
-        }
-        Err(e) => {
-            eprintln!("❌ Failed to start backend: {}", e);
-            // Continue anyway - the frontend might be able to connect to an external backend
+    // Make sure only one whodb-core ever gets spawned: if another instance is
+    // already running, hand it our CLI args and exit before touching the backend.
+    let runtime_dir = std::env::temp_dir();
+    let lock_path = runtime_dir.join("whodb-desktop.lock");
+    let socket_path = runtime_dir.join("whodb-desktop.sock");
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let single_instance_listener = match ipc::acquire(&lock_path, &socket_path, &args) {
+        ipc::Instance::Secondary => {
+            println!("[DEBUG] Another instance of WhoDB is already running, exiting");
+            return;
         }
+        ipc::Instance::Primary(listener) => listener,
+    };
+    if let Ok(mut guard) = SINGLE_INSTANCE_PATHS.lock() {
+        *guard = Some((lock_path.clone(), socket_path));
     }
 
-    tauri::Builder::default()
+    // Build (but don't yet run) the Tauri app so we have an `AppHandle` in
+    // hand before the first `start_backend` call - that's what lets
+    // `resolve_core_binary` use the bundled resource-dir resolution on
+    // the very first launch, not just on supervisor-driven restarts.
+    let app = tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
-        .invoke_handler(tauri::generate_handler![greet, get_backend_port])
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            get_backend_port,
+            vault::save_connection,
+            vault::list_connections,
+            vault::load_connection,
+            vault::delete_connection
+        ])
         .on_window_event(|_, event| {
             if matches!(event, tauri::WindowEvent::CloseRequested { .. }) {
                 cleanup_backend();
             }
         })
-        .setup(|app| {
+        .setup(move |app| {
             // Set up Stronghold with built-in Argon2
             let salt_path = app
                 .path()
@@ -212,6 +496,17 @@ fn main() {
                 tauri_plugin_stronghold::Builder::with_argon2(&salt_path).build()
             )?;
 
+            // A second launch forwards its args here; focus the existing window
+            // instead of letting it spawn a competing backend.
+            let focus_handle = app.handle().clone();
+            ipc::spawn_listener(single_instance_listener, move |_args| {
+                if let Some(window) = focus_handle.get_webview_window("main") {
+                    let _ = window.unminimize();
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+            });
+
             #[cfg(debug_assertions)]
             {
                 // Open developer tools in debug builds
@@ -221,6 +516,28 @@ fn main() {
             }
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application");
+
+    let app_handle = app.handle().clone();
+
+    // Start the backend process, now with an AppHandle so the resource-dir
+    // resolution strategy in `resolve_core_binary` is available from the very first launch.
+    match start_backend(Some(&app_handle)) {
+        Ok(backend_info) => {
+            println!("🚀 Started WhoDB backend on port {}", backend_info.port);
+
+            if let Ok(mut info) = BACKEND_INFO.lock() {
+                *info = Some(backend_info);
+            }
+
+            spawn_supervisor(app_handle);
+        }
+        Err(e) => {
+            eprintln!("❌ Failed to start backend: {}", e);
+            // Continue anyway - the frontend might be able to connect to an external backend
+        }
+    }
+
+    app.run(|_app_handle, _event| {});
 }
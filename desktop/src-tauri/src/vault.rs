@@ -0,0 +1,159 @@
+// Connection-credential vault: persists saved database login profiles in the
+// Stronghold store set up in `main`'s `setup` hook, so a user can reconnect
+// with one click instead of re-entering host/user/password every launch.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_stronghold::stronghold::StrongholdCollection;
+
+// Fixed client path: all connection profiles live in a single Stronghold
+// client/vault, keyed by profile id within the store.
+const CLIENT_PATH: &[u8] = b"whodb-connections";
+const INDEX_KEY: &[u8] = b"__profile_ids";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TlsSettings {
+    pub enabled: bool,
+    pub ca_cert: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionProfile {
+    pub id: String,
+    pub name: String,
+    pub db_type: String,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    pub tls: Option<TlsSettings>,
+}
+
+/// Same as `ConnectionProfile` but without the password or TLS settings, for listing profiles
+/// without exposing decrypted secrets any more than necessary.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionSummary {
+    pub id: String,
+    pub name: String,
+    pub db_type: String,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+}
+
+impl From<&ConnectionProfile> for ConnectionSummary {
+    fn from(profile: &ConnectionProfile) -> Self {
+        ConnectionSummary {
+            id: profile.id.clone(),
+            name: profile.name.clone(),
+            db_type: profile.db_type.clone(),
+            host: profile.host.clone(),
+            port: profile.port,
+            username: profile.username.clone(),
+        }
+    }
+}
+
+fn vault_path(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    app.path()
+        .app_local_data_dir()
+        .map(|dir| dir.join("vault.hold"))
+        .map_err(|e| format!("could not resolve app local data path: {}", e))
+}
+
+fn with_store<F, R>(app: &AppHandle, f: F) -> Result<R, String>
+where
+    F: FnOnce(&tauri_plugin_stronghold::stronghold::Store) -> Result<R, String>,
+{
+    let collection = app.state::<StrongholdCollection>();
+    let path = vault_path(app)?;
+    let stronghold = collection
+        .get_or_load(&path)
+        .map_err(|e| format!("failed to open vault: {}", e))?;
+    let client = stronghold
+        .load_client(CLIENT_PATH)
+        .or_else(|_| stronghold.create_client(CLIENT_PATH))
+        .map_err(|e| format!("failed to open connections vault client: {}", e))?;
+
+    let result = f(&client.store())?;
+
+    stronghold
+        .save()
+        .map_err(|e| format!("failed to persist vault: {}", e))?;
+
+    Ok(result)
+}
+
+fn read_index(store: &tauri_plugin_stronghold::stronghold::Store) -> Result<Vec<String>, String> {
+    match store.get(INDEX_KEY).map_err(|e| e.to_string())? {
+        Some(bytes) => serde_json::from_slice(&bytes).map_err(|e| e.to_string()),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn write_index(
+    store: &tauri_plugin_stronghold::stronghold::Store,
+    ids: &[String],
+) -> Result<(), String> {
+    let bytes = serde_json::to_vec(ids).map_err(|e| e.to_string())?;
+    store.insert(INDEX_KEY.to_vec(), bytes, None).map_err(|e| e.to_string())
+}
+
+/// Encrypts and stores a connection profile, adding its id to the index if new.
+#[tauri::command]
+pub fn save_connection(app: AppHandle, profile: ConnectionProfile) -> Result<(), String> {
+    with_store(&app, |store| {
+        let mut ids = read_index(store)?;
+        if !ids.contains(&profile.id) {
+            ids.push(profile.id.clone());
+            write_index(store, &ids)?;
+        }
+
+        let key = profile.id.as_bytes().to_vec();
+        let value = serde_json::to_vec(&profile).map_err(|e| e.to_string())?;
+        store.insert(key, value, None).map_err(|e| e.to_string())
+    })
+}
+
+/// Lists saved connection profiles without their decrypted passwords.
+#[tauri::command]
+pub fn list_connections(app: AppHandle) -> Result<Vec<ConnectionSummary>, String> {
+    with_store(&app, |store| {
+        let ids = read_index(store)?;
+        let mut summaries = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(bytes) = store.get(id.as_bytes()).map_err(|e| e.to_string())? {
+                let profile: ConnectionProfile =
+                    serde_json::from_slice(&bytes).map_err(|e| e.to_string())?;
+                summaries.push(ConnectionSummary::from(&profile));
+            }
+        }
+        Ok(summaries)
+    })
+}
+
+/// Loads a single connection profile, password included, so the frontend can
+/// auto-reconnect without prompting the user again.
+#[tauri::command]
+pub fn load_connection(app: AppHandle, id: String) -> Result<ConnectionProfile, String> {
+    with_store(&app, |store| {
+        store
+            .get(id.as_bytes())
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("no saved connection with id {}", id))
+            .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|e| e.to_string()))
+    })
+}
+
+/// Removes a saved connection profile and its id from the index.
+#[tauri::command]
+pub fn delete_connection(app: AppHandle, id: String) -> Result<(), String> {
+    with_store(&app, |store| {
+        store.delete(id.as_bytes()).map_err(|e| e.to_string())?;
+        let ids: Vec<String> = read_index(store)?
+            .into_iter()
+            .filter(|existing| existing != &id)
+            .collect();
+        write_index(store, &ids)
+    })
+}